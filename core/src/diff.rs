@@ -25,13 +25,176 @@ pub fn diff_files(old: &str, new: &str, _filename: &str) -> String {
     result
 }
 
-/// Apply an edit by replacing old_content with new_content in the file
+/// Normalize `source` for whitespace-tolerant matching: leading/trailing
+/// whitespace is stripped per line and runs of internal whitespace are
+/// collapsed to a single space, while line breaks are preserved so
+/// multi-line snippets still match as a block.
+///
+/// Returns the normalized string alongside a byte-for-byte offset map
+/// (`map[i]` is the byte offset in `source` that normalized byte `i` came
+/// from, with a trailing sentinel of `source.len()`), so a match found in
+/// the normalized text can be translated back to a byte range in `source`.
+fn normalize_with_offsets(source: &str) -> (String, Vec<usize>) {
+    let mut normalized = String::new();
+    let mut offsets = Vec::new();
+
+    for line in source.split_inclusive('\n') {
+        let line_start = line.as_ptr() as usize - source.as_ptr() as usize;
+        let without_newline = line.trim_end_matches(['\n', '\r']);
+        let leading_ws = without_newline.len() - without_newline.trim_start().len();
+        let core = without_newline.trim();
+        let core_start = line_start + leading_ws;
+
+        let mut prev_was_space = false;
+        for (byte_idx, ch) in core.char_indices() {
+            let abs_offset = core_start + byte_idx;
+            if ch.is_whitespace() {
+                if !prev_was_space {
+                    normalized.push(' ');
+                    offsets.push(abs_offset);
+                }
+                prev_was_space = true;
+            } else {
+                normalized.push(ch);
+                for _ in 0..ch.len_utf8() {
+                    offsets.push(abs_offset);
+                }
+                prev_was_space = false;
+            }
+        }
+
+        if line.ends_with('\n') {
+            normalized.push('\n');
+            offsets.push(line_start + without_newline.len());
+        }
+    }
+
+    offsets.push(source.len());
+    (normalized, offsets)
+}
+
+/// Apply an edit by replacing `old_content` with `new_content` in `content`.
+///
+/// Tries an exact match first. If none is found, falls back to a
+/// whitespace-normalized match (ignoring leading/trailing whitespace per
+/// line and collapsing internal whitespace runs) so reformatted or
+/// imperfectly-indented snippets still apply, mapping the normalized match
+/// back to the original byte range before splicing.
+///
+/// By default, exactly one match must exist, or this errors with the number
+/// found. Pass `occurrence` (0-indexed) to pick a specific match when
+/// several are expected.
+///
+/// Returns `(new_content, start, end)` where `start`/`end` are the byte
+/// range replaced in `content`, so callers can feed it to `diff_files` for a
+/// precise diff.
 #[pyfunction]
-pub fn apply_edit(content: &str, old_content: &str, new_content: &str) -> PyResult<String> {
-    if !content.contains(old_content) {
+#[pyo3(signature = (content, old_content, new_content, occurrence=None))]
+pub fn apply_edit(
+    content: &str,
+    old_content: &str,
+    new_content: &str,
+    occurrence: Option<usize>,
+) -> PyResult<(String, usize, usize)> {
+    let exact_matches: Vec<(usize, usize)> = content
+        .match_indices(old_content)
+        .map(|(start, matched)| (start, start + matched.len()))
+        .collect();
+
+    let (matches, normalized) = if !exact_matches.is_empty() {
+        (exact_matches, false)
+    } else {
+        let (normalized_content, offsets) = normalize_with_offsets(content);
+        let (normalized_old, _) = normalize_with_offsets(old_content);
+
+        let matches: Vec<(usize, usize)> = if normalized_old.is_empty() {
+            Vec::new()
+        } else {
+            normalized_content
+                .match_indices(normalized_old.as_str())
+                .map(|(start, matched)| (offsets[start], offsets[start + matched.len()]))
+                .collect()
+        };
+        (matches, true)
+    };
+
+    if matches.is_empty() {
         return Err(pyo3::exceptions::PyValueError::new_err(
-            "Could not find content to replace"
+            "Could not find content to replace",
         ));
     }
-    Ok(content.replacen(old_content, new_content, 1))
+
+    let (start, end) = match occurrence {
+        Some(idx) => *matches.get(idx).ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err(format!(
+                "Occurrence {idx} out of range: found {} match(es)",
+                matches.len()
+            ))
+        })?,
+        None => {
+            if matches.len() != 1 {
+                let suffix = if normalized { " (whitespace-normalized)" } else { "" };
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "Expected exactly one match{suffix}, found {}",
+                    matches.len()
+                )));
+            }
+            matches[0]
+        }
+    };
+
+    let mut result = String::with_capacity(content.len() - (end - start) + new_content.len());
+    result.push_str(&content[..start]);
+    result.push_str(new_content);
+    result.push_str(&content[end..]);
+
+    Ok((result, start, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exact_match_still_prefers_byte_for_byte_replacement() {
+        let (result, start, end) = apply_edit("foo bar baz", "bar", "BAR", None).unwrap();
+        assert_eq!(result, "foo BAR baz");
+        assert_eq!((start, end), (4, 7));
+        assert_eq!(&"foo bar baz"[start..end], "bar");
+    }
+
+    #[test]
+    fn whitespace_normalized_fallback_matches_reindented_snippet() {
+        let content = "def f():\n    if x:\n        return 1\n";
+        // Differs in indentation from `content`.
+        let old = "if x:\n  return 1";
+        let (result, start, end) = apply_edit(content, old, "if x:\n    return 2", None).unwrap();
+
+        assert_eq!(result, "def f():\n    if x:\n    return 2\n");
+        // The mapped range must point at the real match in the original
+        // (indented) source, not at some offset into the normalized text.
+        assert_eq!(&content[start..end], "if x:\n        return 1");
+    }
+
+    #[test]
+    fn errors_with_match_count_when_not_exactly_one() {
+        let content = "x = 1\nx = 1\n";
+        let err = apply_edit(content, "x = 1", "x = 2", None).unwrap_err();
+        assert!(err.to_string().contains('2'));
+    }
+
+    #[test]
+    fn occurrence_selects_a_specific_match() {
+        let content = "x = 1\nx = 1\n";
+        let (result, start, end) = apply_edit(content, "x = 1", "x = 2", Some(1)).unwrap();
+        assert_eq!(result, "x = 1\nx = 2\n");
+        assert_eq!(&content[start..end], "x = 1");
+        assert_eq!(start, 6);
+    }
+
+    #[test]
+    fn errors_when_nothing_matches_even_after_normalizing() {
+        let err = apply_edit("foo bar", "does not exist", "new", None).unwrap_err();
+        assert!(err.to_string().contains("Could not find"));
+    }
 }
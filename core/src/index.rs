@@ -1,17 +1,20 @@
 use pyo3::prelude::*;
 use ignore::WalkBuilder;
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::process::Command;
 
 /// List all files in a directory, respecting .gitignore
 #[pyfunction]
 #[pyo3(signature = (root, max_depth=None))]
 pub fn list_files(root: &str, max_depth: Option<usize>) -> PyResult<Vec<String>> {
     let mut files = Vec::new();
-    
+
     let mut builder = WalkBuilder::new(root);
     if let Some(depth) = max_depth {
         builder.max_depth(Some(depth));
     }
-    
+
     for entry in builder.build() {
         match entry {
             Ok(e) => {
@@ -24,7 +27,154 @@ pub fn list_files(root: &str, max_depth: Option<usize>) -> PyResult<Vec<String>>
             Err(_) => continue,
         }
     }
-    
+
+    files.sort();
+    Ok(files)
+}
+
+/// Run a git command in `repo_root` and return its stdout lines, ignoring
+/// failures (e.g. `repo_root` not being a git repo) by returning no lines.
+fn git_lines(repo_root: &str, args: &[&str]) -> Vec<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(repo_root)
+        .output();
+
+    match output {
+        Ok(out) if out.status.success() => String::from_utf8_lossy(&out.stdout)
+            .lines()
+            .filter(|l| !l.is_empty())
+            .map(|l| l.to_string())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// List files changed relative to `base_ref` (default `HEAD`), mirroring
+/// `list_files` but scoped to the working set instead of the whole tree.
+///
+/// Combines `git diff --name-only <base_ref>` (modified/renamed/added,
+/// relative to the given ref) with `git ls-files --others
+/// --exclude-standard` (untracked files), drops paths that no longer exist
+/// (deletions), normalizes everything to absolute paths, and returns them
+/// sorted.
+#[pyfunction]
+#[pyo3(signature = (root, base_ref=None))]
+pub fn list_changed_files(root: &str, base_ref: Option<&str>) -> PyResult<Vec<String>> {
+    let base_ref = base_ref.unwrap_or("HEAD");
+    let root_path = Path::new(root);
+
+    let mut paths: BTreeSet<String> = BTreeSet::new();
+    for line in git_lines(root, &["diff", "--name-only", base_ref]) {
+        paths.insert(line);
+    }
+    for line in git_lines(root, &["ls-files", "--others", "--exclude-standard"]) {
+        paths.insert(line);
+    }
+
+    let mut files = Vec::new();
+    for rel in paths {
+        let joined = root_path.join(&rel);
+        if !joined.is_file() {
+            continue;
+        }
+        // `root` may itself be relative; canonicalize so callers always get
+        // absolute paths regardless of how `root` was passed in.
+        if let Ok(abs) = joined.canonicalize() {
+            if let Some(path) = abs.to_str() {
+                files.push(path.to_string());
+            }
+        }
+    }
+
     files.sort();
     Ok(files)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn git(repo: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(args)
+            .current_dir(repo)
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    fn temp_repo(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "codesm-index-test-{}-{}",
+            std::process::id(),
+            name
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        git(&dir, &["init", "-q"]);
+        git(&dir, &["config", "user.email", "test@example.com"]);
+        git(&dir, &["config", "user.name", "Test"]);
+
+        std::fs::write(dir.join("committed.txt"), "one\n").unwrap();
+        git(&dir, &["add", "."]);
+        git(&dir, &["commit", "-q", "-m", "initial"]);
+
+        dir
+    }
+
+    #[test]
+    fn reports_modified_added_and_untracked_files_as_absolute_paths() {
+        let dir = temp_repo("modified-added-untracked");
+
+        std::fs::write(dir.join("committed.txt"), "one\ntwo\n").unwrap();
+        std::fs::write(dir.join("staged.txt"), "new\n").unwrap();
+        git(&dir, &["add", "staged.txt"]);
+        std::fs::write(dir.join("untracked.txt"), "new\n").unwrap();
+
+        let files = list_changed_files(dir.to_str().unwrap(), None).unwrap();
+        let names: Vec<String> = files
+            .iter()
+            .map(|f| Path::new(f).file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+
+        assert_eq!(
+            names,
+            vec!["committed.txt", "staged.txt", "untracked.txt"]
+        );
+        for file in &files {
+            assert!(Path::new(file).is_absolute());
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn deleted_files_are_not_reported() {
+        let dir = temp_repo("deleted");
+
+        std::fs::remove_file(dir.join("committed.txt")).unwrap();
+
+        let files = list_changed_files(dir.to_str().unwrap(), None).unwrap();
+        assert!(files.is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn non_git_directory_yields_no_changes() {
+        let dir = std::env::temp_dir().join(format!(
+            "codesm-index-test-{}-not-a-repo",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("file.txt"), "hi\n").unwrap();
+
+        let files = list_changed_files(dir.to_str().unwrap(), None).unwrap();
+        assert!(files.is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
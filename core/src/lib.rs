@@ -4,11 +4,17 @@ mod diff;
 mod sandbox;
 mod index;
 mod platform;
+mod rank;
+mod watch;
+mod symbols;
 
 use diff::{diff_files, apply_edit};
-use sandbox::execute_command;
-use index::list_files;
-use platform::get_platform_info;
+use sandbox::{execute_command, execute_command_streaming, ExecutionResult};
+use index::{list_files, list_changed_files};
+use platform::{get_platform_info, which};
+use rank::{bump, ranked};
+use watch::{watch_directory, WatchHandle};
+use symbols::{build_symbol_index, Symbol};
 
 /// Python module for codesm core functionality
 #[pymodule]
@@ -16,7 +22,17 @@ fn codesm_core(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(diff_files, m)?)?;
     m.add_function(wrap_pyfunction!(apply_edit, m)?)?;
     m.add_function(wrap_pyfunction!(execute_command, m)?)?;
+    m.add_function(wrap_pyfunction!(execute_command_streaming, m)?)?;
     m.add_function(wrap_pyfunction!(list_files, m)?)?;
+    m.add_function(wrap_pyfunction!(list_changed_files, m)?)?;
     m.add_function(wrap_pyfunction!(get_platform_info, m)?)?;
+    m.add_function(wrap_pyfunction!(which, m)?)?;
+    m.add_function(wrap_pyfunction!(bump, m)?)?;
+    m.add_function(wrap_pyfunction!(ranked, m)?)?;
+    m.add_function(wrap_pyfunction!(watch_directory, m)?)?;
+    m.add_function(wrap_pyfunction!(build_symbol_index, m)?)?;
+    m.add_class::<ExecutionResult>()?;
+    m.add_class::<WatchHandle>()?;
+    m.add_class::<Symbol>()?;
     Ok(())
 }
@@ -1,5 +1,6 @@
 use pyo3::prelude::*;
 use std::env;
+use std::path::{Path, PathBuf};
 
 /// Get platform information
 #[pyfunction]
@@ -9,3 +10,179 @@ pub fn get_platform_info() -> PyResult<(String, String, String)> {
     let family = env::consts::FAMILY.to_string();
     Ok((os, arch, family))
 }
+
+/// Return true if `path` is executable on this platform: the executable bit
+/// on Unix, or a name matching one of the `PATHEXT` extensions on Windows
+/// (checked case-insensitively, since Windows file lookups are).
+fn is_executable(path: &Path) -> bool {
+    if env::consts::FAMILY == "windows" {
+        let pathext = env::var("PATHEXT").unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".to_string());
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| format!(".{e}").to_uppercase());
+        match ext {
+            Some(ext) => pathext
+                .split(';')
+                .any(|candidate| candidate.to_uppercase() == ext),
+            None => false,
+        }
+    } else {
+        #[cfg(unix)]
+        let executable = {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::metadata(path)
+                .map(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+                .unwrap_or(false)
+        };
+        #[cfg(not(unix))]
+        let executable = path.is_file();
+        executable
+    }
+}
+
+/// Candidate file names to probe for `command` in a single `PATH` directory:
+/// on Windows, when `command` contains no `.`, the name with each `PATHEXT`
+/// extension in order followed by the bare name; otherwise just the bare name.
+fn candidate_names(command: &str) -> Vec<String> {
+    if env::consts::FAMILY == "windows" && !command.contains('.') {
+        let pathext = env::var("PATHEXT").unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".to_string());
+        let mut names: Vec<String> = pathext
+            .split(';')
+            .filter(|e| !e.is_empty())
+            .map(|ext| format!("{command}{ext}"))
+            .collect();
+        names.push(command.to_string());
+        names
+    } else {
+        vec![command.to_string()]
+    }
+}
+
+/// Resolve `command` to its full executable path(s) by scanning `PATH`,
+/// mirroring the shell's `which`. Checks the executable bit on Unix and
+/// probes `PATHEXT` extensions on Windows.
+///
+/// Returns every match across `PATH`, in `PATH` order, or an empty list if
+/// nothing resolves. Pass `first_only=True` to stop at (and return only) the
+/// first match.
+#[pyfunction]
+#[pyo3(signature = (command, first_only=false))]
+pub fn which(command: &str, first_only: bool) -> PyResult<Vec<String>> {
+    let path_var = env::var("PATH").unwrap_or_default();
+    Ok(which_with_path(command, first_only, &path_var))
+}
+
+/// Core of `which`, taking the `PATH` value explicitly so it can be tested
+/// against a fake `PATH` without mutating the real process environment.
+fn which_with_path(command: &str, first_only: bool, path_var: &str) -> Vec<String> {
+    let names = candidate_names(command);
+    let mut matches = Vec::new();
+
+    for dir in env::split_paths(path_var) {
+        for name in &names {
+            let candidate: PathBuf = dir.join(name);
+            if candidate.is_file() && is_executable(&candidate) {
+                if let Some(path) = candidate.to_str() {
+                    matches.push(path.to_string());
+                    if first_only {
+                        return matches;
+                    }
+                }
+            }
+        }
+    }
+
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create an empty temp directory on disk. Unique per test via `name`
+    /// plus the process id, so parallel test runs don't collide.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "codesm-platform-test-{}-{}",
+            std::process::id(),
+            name
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[cfg(unix)]
+    fn write_executable(path: &Path, contents: &str) {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::write(path, contents).unwrap();
+        let mut perms = std::fs::metadata(path).unwrap().permissions();
+        perms.set_mode(0o755);
+        std::fs::set_permissions(path, perms).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn finds_an_executable_on_a_fake_path() {
+        let dir = temp_dir("finds-on-path");
+        write_executable(&dir.join("mytool"), "#!/bin/sh\necho hi\n");
+
+        let matches = which_with_path("mytool", false, dir.to_str().unwrap());
+        assert_eq!(matches, vec![dir.join("mytool").to_str().unwrap().to_string()]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn skips_a_non_executable_file_with_a_matching_name() {
+        let dir = temp_dir("skips-non-executable");
+        std::fs::write(dir.join("mytool"), "not executable\n").unwrap();
+
+        let matches = which_with_path("mytool", false, dir.to_str().unwrap());
+        assert!(matches.is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn first_only_stops_after_the_first_path_entry() {
+        let first = temp_dir("first-only-a");
+        let second = temp_dir("first-only-b");
+        write_executable(&first.join("mytool"), "#!/bin/sh\necho first\n");
+        write_executable(&second.join("mytool"), "#!/bin/sh\necho second\n");
+
+        let path_var = std::env::join_paths([&first, &second]).unwrap();
+        let matches = which_with_path("mytool", true, path_var.to_str().unwrap());
+
+        assert_eq!(matches, vec![first.join("mytool").to_str().unwrap().to_string()]);
+
+        std::fs::remove_dir_all(&first).unwrap();
+        std::fs::remove_dir_all(&second).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn without_first_only_every_path_match_is_returned() {
+        let first = temp_dir("all-matches-a");
+        let second = temp_dir("all-matches-b");
+        write_executable(&first.join("mytool"), "#!/bin/sh\necho first\n");
+        write_executable(&second.join("mytool"), "#!/bin/sh\necho second\n");
+
+        let path_var = std::env::join_paths([&first, &second]).unwrap();
+        let matches = which_with_path("mytool", false, path_var.to_str().unwrap());
+
+        assert_eq!(
+            matches,
+            vec![
+                first.join("mytool").to_str().unwrap().to_string(),
+                second.join("mytool").to_str().unwrap().to_string(),
+            ]
+        );
+
+        std::fs::remove_dir_all(&first).unwrap();
+        std::fs::remove_dir_all(&second).unwrap();
+    }
+}
@@ -0,0 +1,193 @@
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const HOUR_SECS: u64 = 60 * 60;
+const DAY_SECS: u64 = 24 * HOUR_SECS;
+const WEEK_SECS: u64 = 7 * DAY_SECS;
+
+/// Below this score an entry is considered noise and aged out of the store.
+const SCORE_FLOOR: f64 = 0.5;
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+struct Entry {
+    count: u64,
+    last_access: u64,
+}
+
+impl Entry {
+    fn score(&self, now: u64) -> f64 {
+        let age = now.saturating_sub(self.last_access);
+        let recency_factor = if age <= HOUR_SECS {
+            4.0
+        } else if age <= DAY_SECS {
+            2.0
+        } else if age <= WEEK_SECS {
+            0.5
+        } else {
+            0.25
+        };
+        self.count as f64 * recency_factor
+    }
+}
+
+fn store_path() -> PathBuf {
+    let dir = dirs::data_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("codesm");
+    dir.join("frecency.json")
+}
+
+fn load_store() -> HashMap<String, Entry> {
+    let path = store_path();
+    fs::read_to_string(&path)
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn save_store(store: &HashMap<String, Entry>) {
+    let path = store_path();
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    if let Ok(contents) = serde_json::to_string(store) {
+        let _ = fs::write(path, contents);
+    }
+}
+
+fn store() -> &'static Mutex<HashMap<String, Entry>> {
+    static STORE: OnceLock<Mutex<HashMap<String, Entry>>> = OnceLock::new();
+    STORE.get_or_init(|| Mutex::new(load_store()))
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Record an access to `path`, bumping its frecency score.
+///
+/// Also prunes entries whose score has decayed below `SCORE_FLOOR` so the
+/// store stays bounded instead of growing forever.
+#[pyfunction]
+pub fn bump(path: &str) -> PyResult<()> {
+    let now = now_secs();
+    let mut store = store().lock().unwrap();
+
+    let entry = store.entry(path.to_string()).or_insert(Entry {
+        count: 0,
+        last_access: now,
+    });
+    entry.count += 1;
+    entry.last_access = now;
+
+    store.retain(|_, entry| entry.score(now) >= SCORE_FLOOR);
+    save_store(&store);
+    Ok(())
+}
+
+/// Sort `paths` by descending frecency score (`count * recency_factor`).
+/// Paths with no recorded access score 0 and sort last, preserving their
+/// relative order.
+#[pyfunction]
+pub fn ranked(paths: Vec<String>) -> PyResult<Vec<String>> {
+    let now = now_secs();
+    let store = store().lock().unwrap();
+
+    let mut scored: Vec<(f64, String)> = paths
+        .into_iter()
+        .map(|path| {
+            let score = store.get(&path).map(|e| e.score(now)).unwrap_or(0.0);
+            (score, path)
+        })
+        .collect();
+
+    scored.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+    Ok(scored.into_iter().map(|(_, path)| path).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(count: u64, last_access: u64) -> Entry {
+        Entry { count, last_access }
+    }
+
+    #[test]
+    fn scores_within_the_last_hour_at_4x() {
+        let e = entry(3, 1_000);
+        assert_eq!(e.score(1_000 + HOUR_SECS), 12.0);
+    }
+
+    #[test]
+    fn scores_within_the_last_day_at_2x() {
+        let e = entry(3, 1_000);
+        // Just past the hour bucket, still within a day.
+        assert_eq!(e.score(1_000 + HOUR_SECS + 1), 6.0);
+        assert_eq!(e.score(1_000 + DAY_SECS), 6.0);
+    }
+
+    #[test]
+    fn scores_within_the_last_week_at_half() {
+        let e = entry(3, 1_000);
+        assert_eq!(e.score(1_000 + DAY_SECS + 1), 1.5);
+        assert_eq!(e.score(1_000 + WEEK_SECS), 1.5);
+    }
+
+    #[test]
+    fn scores_older_than_a_week_at_quarter() {
+        let e = entry(3, 1_000);
+        assert_eq!(e.score(1_000 + WEEK_SECS + 1), 0.75);
+    }
+
+    #[test]
+    fn single_access_older_than_a_week_falls_below_the_floor() {
+        // count=1 at the "otherwise" bucket (0.25) is below SCORE_FLOOR
+        // (0.5), so bump()'s retain() call would prune it.
+        let e = entry(1, 1_000);
+        assert!(e.score(1_000 + WEEK_SECS + 1) < SCORE_FLOOR);
+    }
+
+    #[test]
+    fn repeated_access_older_than_a_week_stays_above_the_floor() {
+        let e = entry(2, 1_000);
+        assert!(e.score(1_000 + WEEK_SECS + 1) >= SCORE_FLOOR);
+    }
+
+    #[test]
+    fn ranked_orders_by_descending_score_with_ties_stable() {
+        let now = 10_000;
+        let mut store = HashMap::new();
+        store.insert("hot".to_string(), entry(10, now));
+        store.insert("warm".to_string(), entry(1, now));
+        // "cold" and "unknown" both score 0 at `now`: "cold" has a stale
+        // entry older than a week with count 0 (never actually producible by
+        // bump(), but score() treats it the same as "no entry").
+        store.insert("cold".to_string(), entry(0, now - WEEK_SECS - 1));
+
+        let mut scored: Vec<(f64, &str)> = vec![
+            (
+                store.get("hot").unwrap().score(now),
+                "hot",
+            ),
+            (
+                store.get("warm").unwrap().score(now),
+                "warm",
+            ),
+            (store.get("cold").unwrap().score(now), "cold"),
+            (0.0, "unknown"),
+        ];
+        scored.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+
+        let order: Vec<&str> = scored.into_iter().map(|(_, path)| path).collect();
+        assert_eq!(order, vec!["hot", "warm", "cold", "unknown"]);
+    }
+}
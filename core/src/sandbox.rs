@@ -1,29 +1,450 @@
 use pyo3::prelude::*;
-use std::process::Command;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::process::{Child, Command, Stdio};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 
-/// Execute a shell command with timeout
-/// Returns (stdout, stderr, exit_code)
-#[pyfunction]
-#[pyo3(signature = (command, cwd=None, _timeout_secs=120))]
-pub fn execute_command(
-    command: &str,
-    cwd: Option<&str>,
-    _timeout_secs: u64,
-) -> PyResult<(String, String, i32)> {
+/// Exit code used when a command is killed for exceeding its timeout,
+/// matching the convention of the `timeout(1)` coreutil.
+const TIMEOUT_EXIT_CODE: i32 = 124;
+
+/// How long to wait after SIGTERM before escalating to SIGKILL (Unix only).
+const KILL_GRACE_PERIOD: Duration = Duration::from_secs(2);
+
+/// How often to poll the child for exit while waiting.
+const POLL_INTERVAL: Duration = Duration::from_millis(25);
+
+/// Result of running a command in the sandbox.
+#[pyclass]
+#[derive(Clone)]
+pub struct ExecutionResult {
+    #[pyo3(get)]
+    pub stdout: String,
+    #[pyo3(get)]
+    pub stderr: String,
+    #[pyo3(get)]
+    pub exit_code: i32,
+    #[pyo3(get)]
+    pub timed_out: bool,
+}
+
+fn build_command(command: &str, cwd: Option<&str>, env: Option<&HashMap<String, String>>) -> Command {
     let mut cmd = Command::new("sh");
     cmd.arg("-c").arg(command);
-    
+
     if let Some(dir) = cwd {
         cmd.current_dir(dir);
     }
-    
-    let output = cmd
-        .output()
+    if let Some(vars) = env {
+        for (key, value) in vars {
+            cmd.env(key, value);
+        }
+    }
+    ProcessGroup::configure(&mut cmd);
+    cmd
+}
+
+#[cfg(windows)]
+mod job {
+    use std::io;
+    use windows_sys::Win32::Foundation::HANDLE;
+    use windows_sys::Win32::System::JobObjects::{
+        AssignProcessToJobObject, CreateJobObjectW, JobObjectExtendedLimitInformation,
+        SetInformationJobObject, TerminateJobObject, JOBOBJECT_EXTENDED_LIMIT_INFORMATION,
+        JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+    };
+    use windows_sys::Win32::Foundation::CloseHandle;
+
+    /// A Windows job object that owns the child process (and anything it
+    /// spawns), so terminating the job terminates the whole tree instead of
+    /// just the immediate `sh.exe` process.
+    pub struct JobHandle(HANDLE);
+
+    impl JobHandle {
+        pub fn new() -> Option<Self> {
+            unsafe {
+                let job = CreateJobObjectW(std::ptr::null(), std::ptr::null());
+                if job == 0 {
+                    return None;
+                }
+                let mut info: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = std::mem::zeroed();
+                info.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+                let ok = SetInformationJobObject(
+                    job,
+                    JobObjectExtendedLimitInformation,
+                    &info as *const _ as *const _,
+                    std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+                );
+                if ok == 0 {
+                    CloseHandle(job);
+                    return None;
+                }
+                Some(JobHandle(job))
+            }
+        }
+
+        pub fn assign(&self, process: HANDLE) -> io::Result<()> {
+            let ok = unsafe { AssignProcessToJobObject(self.0, process) };
+            if ok == 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        }
+
+        pub fn terminate(&self) {
+            unsafe {
+                TerminateJobObject(self.0, 1);
+            }
+        }
+    }
+
+    impl Drop for JobHandle {
+        fn drop(&mut self) {
+            unsafe {
+                CloseHandle(self.0);
+            }
+        }
+    }
+}
+
+/// Owns whatever platform mechanism is needed to terminate a spawned
+/// command's entire process tree (not just the immediate `sh` child), so a
+/// command that backgrounds or forks children (`sleep 100 &`, a pipeline, a
+/// build wrapper) doesn't outlive its declared timeout.
+#[cfg(unix)]
+struct ProcessGroup;
+
+#[cfg(windows)]
+struct ProcessGroup(Option<job::JobHandle>);
+
+impl ProcessGroup {
+    /// Configure `cmd` to spawn into its own process group (Unix) so it can
+    /// later be signaled as a unit. On Windows the job object is created
+    /// lazily in `attach`, since it needs the spawned child's handle.
+    #[cfg(unix)]
+    fn configure(cmd: &mut Command) {
+        use std::os::unix::process::CommandExt;
+        // pgid 0 means "use the child's own pid as the group id", i.e. a
+        // fresh group containing just this command and its descendants.
+        cmd.process_group(0);
+    }
+
+    #[cfg(windows)]
+    fn configure(_cmd: &mut Command) {}
+
+    /// Create the process-group handle for an already-spawned `child`.
+    #[cfg(unix)]
+    fn attach(_child: &Child) -> ProcessGroup {
+        ProcessGroup
+    }
+
+    #[cfg(windows)]
+    fn attach(child: &Child) -> ProcessGroup {
+        use std::os::windows::io::AsRawHandle;
+        let job = job::JobHandle::new();
+        if let Some(job) = &job {
+            let _ = job.assign(child.as_raw_handle() as _);
+        }
+        ProcessGroup(job)
+    }
+
+    /// Ask the whole group to exit gracefully (SIGTERM on Unix; Windows has
+    /// no equivalent, so this is a no-op there and `kill` does the work).
+    #[cfg(unix)]
+    fn terminate(&self, child: &Child) {
+        unsafe {
+            libc::kill(-(child.id() as libc::pid_t), libc::SIGTERM);
+        }
+    }
+
+    #[cfg(windows)]
+    fn terminate(&self, _child: &Child) {}
+
+    /// Forcibly kill the whole group (SIGKILL on Unix, `TerminateJobObject`
+    /// on Windows, falling back to `Child::kill` if no job was created).
+    #[cfg(unix)]
+    fn kill(&self, child: &mut Child) {
+        unsafe {
+            libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+        }
+        let _ = child.wait();
+    }
+
+    #[cfg(windows)]
+    fn kill(&self, child: &mut Child) {
+        match &self.0 {
+            Some(job) => job.terminate(),
+            None => {
+                let _ = child.kill();
+            }
+        }
+        let _ = child.wait();
+    }
+}
+
+/// Spawn a thread that drains a child pipe to completion, returning its bytes.
+fn spawn_reader<R: Read + Send + 'static>(pipe: Option<R>) -> JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        buf
+    })
+}
+
+/// Spawn a thread that writes `bytes` to `pipe` and then closes it.
+///
+/// Writing stdin must happen concurrently with draining stdout/stderr: a
+/// child that fills its stdout pipe before reading all of stdin (or vice
+/// versa) would otherwise deadlock the calling thread against the child, and
+/// the timeout in `wait_with_timeout` would never get a chance to fire since
+/// we'd still be blocked in `write_all`.
+fn spawn_writer<W: Write + Send + 'static>(pipe: Option<W>, bytes: Option<Vec<u8>>) -> JoinHandle<()> {
+    thread::spawn(move || {
+        if let Some(mut pipe) = pipe {
+            if let Some(bytes) = bytes {
+                let _ = pipe.write_all(&bytes);
+            }
+            // Drop here (end of scope) so commands reading stdin to EOF don't
+            // hang waiting for more input.
+        }
+    })
+}
+
+/// Poll `child` until it exits or `timeout` elapses. On expiry, terminate
+/// the whole process group (SIGTERM then SIGKILL after a grace period on
+/// Unix, the owning job object on Windows) and report the timeout.
+fn wait_with_timeout(
+    child: &mut Child,
+    group: &ProcessGroup,
+    timeout: Duration,
+) -> PyResult<(i32, bool)> {
+    let start = Instant::now();
+    loop {
+        if let Some(status) = child
+            .try_wait()
+            .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))?
+        {
+            return Ok((status.code().unwrap_or(-1), false));
+        }
+
+        if start.elapsed() >= timeout {
+            group.terminate(child);
+            let term_start = Instant::now();
+            while cfg!(unix) && term_start.elapsed() < KILL_GRACE_PERIOD {
+                if let Some(_status) = child
+                    .try_wait()
+                    .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))?
+                {
+                    return Ok((TIMEOUT_EXIT_CODE, true));
+                }
+                thread::sleep(POLL_INTERVAL);
+            }
+            // Still alive after the grace period (Windows has none): escalate
+            // to a forceful kill of the whole group and wait for it to die.
+            group.kill(child);
+            return Ok((TIMEOUT_EXIT_CODE, true));
+        }
+
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Execute a shell command with a real timeout, optional environment
+/// overrides, and optional stdin.
+///
+/// Unlike a plain `cmd.output()` call this spawns the child and polls it, so
+/// a hung command is actually terminated instead of blocking forever.
+#[pyfunction]
+#[pyo3(signature = (command, cwd=None, timeout_secs=120, env=None, stdin=None))]
+pub fn execute_command(
+    command: &str,
+    cwd: Option<&str>,
+    timeout_secs: u64,
+    env: Option<HashMap<String, String>>,
+    stdin: Option<Vec<u8>>,
+) -> PyResult<ExecutionResult> {
+    let mut cmd = build_command(command, cwd, env.as_ref());
+    cmd.stdin(Stdio::piped());
+    cmd.stdout(Stdio::piped());
+    cmd.stderr(Stdio::piped());
+
+    let mut child = cmd
+        .spawn()
         .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))?;
-    
-    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-    let exit_code = output.status.code().unwrap_or(-1);
-    
-    Ok((stdout, stderr, exit_code))
+    let group = ProcessGroup::attach(&child);
+
+    // Writing stdin and draining stdout/stderr run on their own threads so a
+    // child that fills one pipe before draining another can't deadlock the
+    // calling thread, which polls for exit below (so the timeout is actually
+    // reachable).
+    let stdin_handle = spawn_writer(child.stdin.take(), stdin);
+    let stdout_handle = spawn_reader(child.stdout.take());
+    let stderr_handle = spawn_reader(child.stderr.take());
+
+    let (exit_code, timed_out) =
+        wait_with_timeout(&mut child, &group, Duration::from_secs(timeout_secs))?;
+
+    let _ = stdin_handle.join();
+    let stdout = stdout_handle.join().unwrap_or_default();
+    let stderr = stderr_handle.join().unwrap_or_default();
+
+    Ok(ExecutionResult {
+        stdout: String::from_utf8_lossy(&stdout).to_string(),
+        stderr: String::from_utf8_lossy(&stderr).to_string(),
+        exit_code,
+        timed_out,
+    })
+}
+
+/// Size of the chunks delivered to the streaming callback.
+const STREAM_CHUNK_SIZE: usize = 8192;
+
+/// Stream output from a pipe to `callback(stream_name, chunk: bytes)` as it
+/// arrives, returning the accumulated bytes once the pipe closes.
+fn spawn_streaming_reader<R: Read + Send + 'static>(
+    pipe: Option<R>,
+    stream_name: &'static str,
+    callback: Py<PyAny>,
+) -> JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        let mut chunk = [0u8; STREAM_CHUNK_SIZE];
+        if let Some(mut pipe) = pipe {
+            loop {
+                match pipe.read(&mut chunk) {
+                    Ok(0) => break,
+                    Ok(n) => {
+                        buf.extend_from_slice(&chunk[..n]);
+                        Python::with_gil(|py| {
+                            let _ = callback.call1(py, (stream_name, &chunk[..n]));
+                        });
+                    }
+                    Err(_) => break,
+                }
+            }
+        }
+        buf
+    })
+}
+
+/// Like `execute_command`, but also invokes `callback(stream, chunk)` with raw
+/// stdout/stderr chunks as they arrive, so long-running build/test commands
+/// can report progress to the agent. The full output is still collected and
+/// returned in the `ExecutionResult`.
+#[pyfunction]
+#[pyo3(signature = (command, callback, cwd=None, timeout_secs=120, env=None, stdin=None))]
+pub fn execute_command_streaming(
+    py: Python<'_>,
+    command: &str,
+    callback: Py<PyAny>,
+    cwd: Option<&str>,
+    timeout_secs: u64,
+    env: Option<HashMap<String, String>>,
+    stdin: Option<Vec<u8>>,
+) -> PyResult<ExecutionResult> {
+    let mut cmd = build_command(command, cwd, env.as_ref());
+    cmd.stdin(Stdio::piped());
+    cmd.stdout(Stdio::piped());
+    cmd.stderr(Stdio::piped());
+
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))?;
+    let group = ProcessGroup::attach(&child);
+    let stdin_pipe = child.stdin.take();
+
+    // The reader threads acquire the GIL on every chunk to invoke the
+    // callback, and `wait_with_timeout`/`.join()` below block the calling
+    // thread until the child exits. Without releasing the GIL here, those
+    // two things deadlock on the very first chunk of output. The stdin write
+    // must live inside this block too: writing it beforehand would block the
+    // calling thread (while holding the GIL) on exactly the same pipe
+    // deadlock `wait_with_timeout` is meant to guard against.
+    py.allow_threads(|| -> PyResult<ExecutionResult> {
+        let stderr_callback = Python::with_gil(|py| callback.clone_ref(py));
+        let stdin_handle = spawn_writer(stdin_pipe, stdin);
+        let stdout_handle = spawn_streaming_reader(child.stdout.take(), "stdout", callback);
+        let stderr_handle = spawn_streaming_reader(child.stderr.take(), "stderr", stderr_callback);
+
+        let (exit_code, timed_out) =
+            wait_with_timeout(&mut child, &group, Duration::from_secs(timeout_secs))?;
+
+        let _ = stdin_handle.join();
+        let stdout = stdout_handle.join().unwrap_or_default();
+        let stderr = stderr_handle.join().unwrap_or_default();
+
+        Ok(ExecutionResult {
+            stdout: String::from_utf8_lossy(&stdout).to_string(),
+            stderr: String::from_utf8_lossy(&stderr).to_string(),
+            exit_code,
+            timed_out,
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stdin_is_written_back_through_stdout_without_deadlocking() {
+        // `cat` echoes stdin to stdout; a payload comfortably larger than a
+        // pipe buffer (64KiB on Linux) exercises the case that deadlocks if
+        // stdin is written synchronously before stdout is drained: the child
+        // fills the stdout pipe and blocks on it before it has read all of
+        // stdin, so a same-thread write_all would never return.
+        let payload = vec![b'x'; 256 * 1024];
+        let result = execute_command("cat", None, 10, None, Some(payload.clone())).unwrap();
+        assert!(!result.timed_out);
+        assert_eq!(result.exit_code, 0);
+        assert_eq!(result.stdout.len(), payload.len());
+    }
+
+    #[test]
+    fn a_hung_command_is_terminated_at_the_timeout() {
+        let start = Instant::now();
+        let result = execute_command("sleep 30", None, 1, None, None).unwrap();
+        assert!(result.timed_out);
+        assert_eq!(result.exit_code, TIMEOUT_EXIT_CODE);
+        // The grace period adds at most KILL_GRACE_PERIOD on top of the
+        // 1s timeout; well under the 30s the command itself asked for.
+        assert!(start.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn timeout_kills_the_whole_process_group_not_just_the_shell() {
+        // The backgrounded `sleep` is a grandchild of this process, not a
+        // direct child, so it only dies if the whole process group is
+        // signaled rather than just the immediate `sh -c` process.
+        let marker = std::env::temp_dir().join(format!(
+            "codesm-sandbox-test-group-kill-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&marker);
+        let command = format!(
+            "(sleep 5; touch {}) & wait",
+            marker.to_str().unwrap()
+        );
+
+        let result = execute_command(&command, None, 1, None, None).unwrap();
+        assert!(result.timed_out);
+
+        // Give a leaked, un-killed grandchild time to have created the
+        // marker before asserting it never did.
+        thread::sleep(Duration::from_secs(5));
+        assert!(!marker.exists());
+    }
+
+    #[test]
+    fn exit_code_and_output_are_reported_for_a_normal_command() {
+        let result = execute_command("printf out; printf err 1>&2; exit 7", None, 5, None, None)
+            .unwrap();
+        assert!(!result.timed_out);
+        assert_eq!(result.exit_code, 7);
+        assert_eq!(result.stdout, "out");
+        assert_eq!(result.stderr, "err");
+    }
 }
@@ -0,0 +1,329 @@
+use crate::index::list_files;
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use rustpython_parser::ast::{self, Ranged};
+use rustpython_parser::{parse, Mode};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+
+/// A top-level symbol extracted from a Python source file.
+#[pyclass]
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Symbol {
+    #[pyo3(get)]
+    pub name: String,
+    /// One of "function", "class", "import", "assignment".
+    #[pyo3(get)]
+    pub kind: String,
+    #[pyo3(get)]
+    pub line: usize,
+    #[pyo3(get)]
+    pub col: usize,
+    #[pyo3(get)]
+    pub end_line: usize,
+    #[pyo3(get)]
+    pub end_col: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct CacheEntry {
+    mtime: u64,
+    size: u64,
+    hash: String,
+    symbols: Vec<Symbol>,
+}
+
+fn cache_path() -> PathBuf {
+    let dir = dirs::cache_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("codesm");
+    dir.join("symbol_index.json")
+}
+
+fn load_cache() -> HashMap<String, CacheEntry> {
+    fs::read_to_string(cache_path())
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn save_cache(cache: &HashMap<String, CacheEntry>) {
+    let path = cache_path();
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    if let Ok(contents) = serde_json::to_string(cache) {
+        let _ = fs::write(path, contents);
+    }
+}
+
+/// Convert a byte offset into a (1-indexed line, 0-indexed column) pair.
+fn offset_to_line_col(source: &str, offset: usize) -> (usize, usize) {
+    let offset = offset.min(source.len());
+    let mut line = 1;
+    let mut last_newline = 0;
+    for (idx, byte) in source.as_bytes()[..offset].iter().enumerate() {
+        if *byte == b'\n' {
+            line += 1;
+            last_newline = idx + 1;
+        }
+    }
+    (line, offset - last_newline)
+}
+
+fn span_of<T: Ranged>(source: &str, node: &T) -> (usize, usize, usize, usize) {
+    let range = node.range();
+    let (line, col) = offset_to_line_col(source, range.start().to_usize());
+    let (end_line, end_col) = offset_to_line_col(source, range.end().to_usize());
+    (line, col, end_line, end_col)
+}
+
+fn symbol(source: &str, name: String, kind: &str, node: &impl Ranged) -> Symbol {
+    let (line, col, end_line, end_col) = span_of(source, node);
+    Symbol {
+        name,
+        kind: kind.to_string(),
+        line,
+        col,
+        end_line,
+        end_col,
+    }
+}
+
+/// Extract top-level functions, classes, imports, and assignments from a
+/// parsed module body.
+fn extract_symbols(source: &str, body: &[ast::Stmt]) -> Vec<Symbol> {
+    let mut symbols = Vec::new();
+
+    for stmt in body {
+        match stmt {
+            ast::Stmt::FunctionDef(def) => {
+                symbols.push(symbol(source, def.name.to_string(), "function", def));
+            }
+            ast::Stmt::AsyncFunctionDef(def) => {
+                symbols.push(symbol(source, def.name.to_string(), "function", def));
+            }
+            ast::Stmt::ClassDef(def) => {
+                symbols.push(symbol(source, def.name.to_string(), "class", def));
+            }
+            ast::Stmt::Import(import) => {
+                for alias in &import.names {
+                    let name = alias.asname.as_ref().unwrap_or(&alias.name).to_string();
+                    symbols.push(symbol(source, name, "import", import));
+                }
+            }
+            ast::Stmt::ImportFrom(import) => {
+                for alias in &import.names {
+                    let name = alias.asname.as_ref().unwrap_or(&alias.name).to_string();
+                    symbols.push(symbol(source, name, "import", import));
+                }
+            }
+            ast::Stmt::Assign(assign) => {
+                for target in &assign.targets {
+                    if let ast::Expr::Name(name) = target {
+                        symbols.push(symbol(source, name.id.to_string(), "assignment", assign));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    symbols
+}
+
+fn parse_symbols(path: &std::path::Path, source: &str) -> Vec<Symbol> {
+    let filename = path.to_string_lossy();
+    match parse(source, Mode::Module, &filename) {
+        Ok(ast::Mod::Module(module)) => extract_symbols(source, &module.body),
+        _ => Vec::new(),
+    }
+}
+
+fn index_one(path: String, cache: &Mutex<HashMap<String, CacheEntry>>) -> (String, Vec<Symbol>) {
+    let meta = match fs::metadata(&path) {
+        Ok(meta) => meta,
+        Err(_) => return (path, Vec::new()),
+    };
+    let size = meta.len();
+    let mtime = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    if let Some(entry) = cache.lock().unwrap().get(&path) {
+        if entry.mtime == mtime && entry.size == size {
+            return (path, entry.symbols.clone());
+        }
+    }
+
+    let source = match fs::read_to_string(&path) {
+        Ok(source) => source,
+        Err(_) => return (path, Vec::new()),
+    };
+    let hash = blake3::hash(source.as_bytes()).to_hex().to_string();
+
+    if let Some(entry) = cache.lock().unwrap().get(&path) {
+        if entry.hash == hash {
+            return (path, entry.symbols.clone());
+        }
+    }
+
+    let symbols = parse_symbols(std::path::Path::new(&path), &source);
+
+    cache.lock().unwrap().insert(
+        path.clone(),
+        CacheEntry {
+            mtime,
+            size,
+            hash,
+            symbols: symbols.clone(),
+        },
+    );
+
+    (path, symbols)
+}
+
+/// Build a top-level symbol index over every Python file under `root`.
+///
+/// Files are discovered with `list_files` (so `.gitignore` is respected),
+/// parsed in parallel with `rayon`, and results are cached on disk keyed by
+/// file mtime+size (fast path) and a blake3 hash of the contents (exact
+/// path), so unchanged files are skipped on re-index.
+#[pyfunction]
+pub fn build_symbol_index(root: &str) -> PyResult<HashMap<String, Vec<Symbol>>> {
+    let files: Vec<String> = list_files(root, None)?
+        .into_iter()
+        .filter(|f| f.ends_with(".py"))
+        .collect();
+
+    let cache = Mutex::new(load_cache());
+
+    let results: Vec<(String, Vec<Symbol>)> =
+        files.into_par_iter().map(|path| index_one(path, &cache)).collect();
+
+    save_cache(&cache.into_inner().unwrap());
+
+    Ok(results.into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_py_file(name: &str, contents: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "codesm-symbols-test-{}-{}",
+            std::process::id(),
+            name
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("module.py");
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    fn sentinel_symbols(name: &str) -> Vec<Symbol> {
+        vec![Symbol {
+            name: name.to_string(),
+            kind: "sentinel".to_string(),
+            line: 0,
+            col: 0,
+            end_line: 0,
+            end_col: 0,
+        }]
+    }
+
+    #[test]
+    fn extracts_top_level_function_class_import_and_assignment() {
+        let source = "import os\nclass Foo:\n    pass\ndef bar():\n    pass\nX = 1\n";
+        let symbols = parse_symbols(Path::new("module.py"), source);
+
+        let kinds: Vec<(&str, &str)> = symbols
+            .iter()
+            .map(|s| (s.name.as_str(), s.kind.as_str()))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("os", "import"),
+                ("Foo", "class"),
+                ("bar", "function"),
+                ("X", "assignment"),
+            ]
+        );
+    }
+
+    #[test]
+    fn cache_hit_via_mtime_and_size_skips_reparsing() {
+        let path = temp_py_file("fast-path", "def real():\n    pass\n");
+        let path_str = path.to_str().unwrap().to_string();
+        let cache = Mutex::new(HashMap::new());
+
+        let (_, real_symbols) = index_one(path_str.clone(), &cache);
+        assert_eq!(real_symbols[0].name, "real");
+
+        // Poison the cached symbols without touching mtime/size: if
+        // `index_one` actually trusts the fast path it must return this
+        // sentinel instead of the real, freshly-reparsed symbols.
+        {
+            let mut cache = cache.lock().unwrap();
+            let entry = cache.get_mut(&path_str).unwrap();
+            entry.symbols = sentinel_symbols("from-fast-path");
+        }
+
+        let (_, symbols) = index_one(path_str.clone(), &cache);
+        assert_eq!(symbols[0].name, "from-fast-path");
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn cache_hit_via_content_hash_when_mtime_size_recorded_stale() {
+        let path = temp_py_file("hash-path", "def real():\n    pass\n");
+        let path_str = path.to_str().unwrap().to_string();
+        let cache = Mutex::new(HashMap::new());
+
+        index_one(path_str.clone(), &cache);
+
+        // Simulate a file that was touched (e.g. by a checkout) without its
+        // content changing: the recorded mtime/size no longer match, but the
+        // content hash still does, so the hash fallback should hit.
+        {
+            let mut cache = cache.lock().unwrap();
+            let entry = cache.get_mut(&path_str).unwrap();
+            entry.mtime = entry.mtime.wrapping_add(1);
+            entry.size += 1;
+            entry.symbols = sentinel_symbols("from-hash-path");
+        }
+
+        let (_, symbols) = index_one(path_str.clone(), &cache);
+        assert_eq!(symbols[0].name, "from-hash-path");
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn content_change_invalidates_the_cache_and_reparses() {
+        // Use a different length for the new name so the size check alone
+        // is guaranteed to invalidate the fast path, independent of mtime
+        // resolution.
+        let path = temp_py_file("reparse", "def old():\n    pass\n");
+        let path_str = path.to_str().unwrap().to_string();
+        let cache = Mutex::new(HashMap::new());
+
+        index_one(path_str.clone(), &cache);
+        std::fs::write(&path, "def a_much_longer_new_function_name():\n    pass\n").unwrap();
+
+        let (_, symbols) = index_one(path_str.clone(), &cache);
+        assert_eq!(symbols[0].name, "a_much_longer_new_function_name");
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,295 @@
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::WalkBuilder;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use pyo3::prelude::*;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Sender, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long the event stream (across all paths) must stay quiet before the
+/// pending batch is flushed to the callback, to coalesce the rapid
+/// create/modify sequences editors and checkouts produce.
+const DEBOUNCE_WINDOW: Duration = Duration::from_millis(200);
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+fn kind_str(kind: &EventKind) -> &'static str {
+    match kind {
+        EventKind::Create(_) => "create",
+        EventKind::Modify(_) => "modify",
+        EventKind::Remove(_) => "delete",
+        _ => "other",
+    }
+}
+
+/// Fold one filesystem `event` into the in-flight `pending` batch: ignored
+/// paths are dropped, event kinds we don't surface are skipped, and a path
+/// touched more than once before the batch flushes keeps only the most
+/// recent kind (coalescing, e.g., a create immediately followed by a
+/// modify into a single "modify" entry).
+fn record_event(pending: &mut HashMap<PathBuf, &'static str>, gitignore: &Gitignore, event: &Event) {
+    let kind = kind_str(&event.kind);
+    if kind == "other" {
+        return;
+    }
+    for path in &event.paths {
+        let is_dir = path.is_dir();
+        if gitignore.matched(path, is_dir).is_ignore() {
+            continue;
+        }
+        pending.insert(path.clone(), kind);
+    }
+}
+
+/// Expand a leading `~` (as produced by e.g. `git config core.excludesfile`)
+/// to the user's home directory.
+fn expand_tilde(path: &str) -> PathBuf {
+    match path.strip_prefix('~') {
+        Some(rest) => dirs::home_dir()
+            .map(|home| home.join(rest.trim_start_matches('/')))
+            .unwrap_or_else(|| PathBuf::from(path)),
+        None => PathBuf::from(path),
+    }
+}
+
+/// Locate the user's global gitignore file, the same one `git` (and the
+/// `ignore` crate's `WalkBuilder` default config) consults for every repo:
+/// `core.excludesfile` if set, otherwise the XDG default.
+fn global_gitignore_path() -> Option<PathBuf> {
+    let output = std::process::Command::new("git")
+        .args(["config", "--global", "--get", "core.excludesfile"])
+        .output()
+        .ok()?;
+    if output.status.success() {
+        let configured = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if !configured.is_empty() {
+            return Some(expand_tilde(&configured));
+        }
+    }
+    dirs::config_dir().map(|dir| dir.join("git").join("ignore"))
+}
+
+/// Build the same effective set of gitignore rules `list_files` applies via
+/// `ignore::WalkBuilder`: every nested per-directory `.gitignore` under
+/// `root`, the repo-local `.git/info/exclude`, and the user's global
+/// gitignore.
+fn build_gitignore(root: &Path) -> Gitignore {
+    let mut builder = GitignoreBuilder::new(root);
+
+    let walker = WalkBuilder::new(root)
+        .hidden(false)
+        .git_ignore(false)
+        .git_global(false)
+        .git_exclude(false)
+        .build();
+    for entry in walker.flatten() {
+        if entry.file_name() == ".gitignore" {
+            builder.add(entry.path());
+        }
+    }
+
+    let exclude = root.join(".git").join("info").join("exclude");
+    if exclude.is_file() {
+        builder.add(&exclude);
+    }
+
+    if let Some(global) = global_gitignore_path() {
+        if global.is_file() {
+            builder.add(&global);
+        }
+    }
+
+    builder.build().unwrap_or_else(|_| Gitignore::empty())
+}
+
+/// Handle to a background filesystem watcher. Dropping or calling `stop`
+/// shuts the watcher thread down.
+#[pyclass]
+pub struct WatchHandle {
+    stop_tx: Option<Sender<()>>,
+}
+
+#[pymethods]
+impl WatchHandle {
+    /// Stop the background watcher thread.
+    fn stop(&mut self) {
+        if let Some(tx) = self.stop_tx.take() {
+            let _ = tx.send(());
+        }
+    }
+}
+
+impl Drop for WatchHandle {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Watch `root` for filesystem changes, respecting the same `.gitignore`
+/// rules as `list_files`, and deliver debounced `(path, kind)` batches to
+/// `callback(events: list[tuple[str, str]])` on a background thread.
+///
+/// Bursts of events for the same path within a short window (e.g. repeated
+/// saves or a checkout touching many files) are coalesced into a single
+/// entry per path, keeping the most recent kind.
+#[pyfunction]
+pub fn watch_directory(root: &str, callback: Py<PyAny>) -> PyResult<WatchHandle> {
+    let root = PathBuf::from(root);
+    let (stop_tx, stop_rx) = channel::<()>();
+    let (event_tx, event_rx) = channel::<Event>();
+
+    let gitignore = build_gitignore(&root);
+
+    let mut watcher: RecommendedWatcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
+        if let Ok(event) = res {
+            let _ = event_tx.send(event);
+        }
+    })
+    .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))?;
+
+    watcher
+        .watch(&root, RecursiveMode::Recursive)
+        .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))?;
+
+    thread::spawn(move || {
+        // Keep the watcher alive for the lifetime of this thread.
+        let _watcher = watcher;
+        let mut pending: HashMap<PathBuf, &'static str> = HashMap::new();
+        let mut last_update = Instant::now();
+
+        loop {
+            match stop_rx.try_recv() {
+                Ok(()) => break,
+                Err(TryRecvError::Disconnected) => break,
+                Err(TryRecvError::Empty) => {}
+            }
+
+            match event_rx.recv_timeout(POLL_INTERVAL) {
+                Ok(event) => {
+                    record_event(&mut pending, &gitignore, &event);
+                    last_update = Instant::now();
+                }
+                Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {}
+                Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => break,
+            }
+
+            if !pending.is_empty() && last_update.elapsed() >= DEBOUNCE_WINDOW {
+                let batch: Vec<(String, &'static str)> = pending
+                    .drain()
+                    .filter_map(|(path, kind)| path.to_str().map(|p| (p.to_string(), kind)))
+                    .collect();
+
+                Python::with_gil(|py| {
+                    let _ = callback.call1(py, (batch,));
+                });
+            }
+        }
+    });
+
+    Ok(WatchHandle {
+        stop_tx: Some(stop_tx),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{CreateKind, ModifyKind, RemoveKind};
+
+    fn event(kind: EventKind, path: &str) -> Event {
+        Event::new(kind).add_path(PathBuf::from(path))
+    }
+
+    #[test]
+    fn a_single_event_is_recorded() {
+        let mut pending = HashMap::new();
+        let gitignore = Gitignore::empty();
+        record_event(
+            &mut pending,
+            &gitignore,
+            &event(EventKind::Create(CreateKind::File), "/repo/a.txt"),
+        );
+        assert_eq!(pending.get(&PathBuf::from("/repo/a.txt")), Some(&"create"));
+    }
+
+    #[test]
+    fn a_burst_for_the_same_path_coalesces_to_the_latest_kind() {
+        let mut pending = HashMap::new();
+        let gitignore = Gitignore::empty();
+
+        record_event(
+            &mut pending,
+            &gitignore,
+            &event(EventKind::Create(CreateKind::File), "/repo/a.txt"),
+        );
+        record_event(
+            &mut pending,
+            &gitignore,
+            &event(EventKind::Modify(ModifyKind::Any), "/repo/a.txt"),
+        );
+        record_event(
+            &mut pending,
+            &gitignore,
+            &event(EventKind::Remove(RemoveKind::File), "/repo/a.txt"),
+        );
+
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending.get(&PathBuf::from("/repo/a.txt")), Some(&"delete"));
+    }
+
+    #[test]
+    fn events_with_no_surfaced_kind_are_skipped() {
+        let mut pending = HashMap::new();
+        let gitignore = Gitignore::empty();
+        record_event(
+            &mut pending,
+            &gitignore,
+            &event(EventKind::Any, "/repo/a.txt"),
+        );
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn ignored_paths_never_enter_the_batch() {
+        let dir = std::env::temp_dir().join(format!(
+            "codesm-watch-test-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(".gitignore"), "*.log\n").unwrap();
+
+        let gitignore = build_gitignore(&dir);
+        let mut pending = HashMap::new();
+        record_event(
+            &mut pending,
+            &gitignore,
+            &event(
+                EventKind::Create(CreateKind::File),
+                dir.join("debug.log").to_str().unwrap(),
+            ),
+        );
+        record_event(
+            &mut pending,
+            &gitignore,
+            &event(
+                EventKind::Create(CreateKind::File),
+                dir.join("main.rs").to_str().unwrap(),
+            ),
+        );
+
+        assert_eq!(pending.len(), 1);
+        assert!(pending.contains_key(&dir.join("main.rs")));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn expand_tilde_joins_the_home_directory() {
+        if let Some(home) = dirs::home_dir() {
+            let expanded = expand_tilde("~/.gitignore_global");
+            assert_eq!(expanded, home.join(".gitignore_global"));
+        }
+    }
+}